@@ -1,11 +1,206 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
-    Manager,
+    menu::{CheckMenuItem, ContextMenu, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    Emitter, Manager,
 };
 
+/// A notebook entry as reported by the frontend for the "Open Recent" submenu.
+#[derive(serde::Deserialize)]
+struct RecentNotebook {
+    id: String,
+    name: String,
+}
+
+/// Handle to the File menu's "Open Recent" submenu so `update_recent` can clear and
+/// repopulate it as the persisted (via `tauri_plugin_sql`) recent-notebooks list changes.
+/// `ids` maps each `recent_<i>` item id to the notebook id it represents, since the
+/// item id itself must stay stable for `on_menu_event` to route it to a fixed event.
+struct RecentMenuState {
+    submenu: Submenu<tauri::Wry>,
+    ids: Mutex<Vec<String>>,
+}
+
+/// Payload for the stable `menu://open-recent` event.
+#[derive(Clone, serde::Serialize)]
+struct OpenRecentEvent {
+    id: String,
+}
+
+#[tauri::command]
+fn update_recent(
+    app: tauri::AppHandle,
+    state: tauri::State<RecentMenuState>,
+    items: Vec<RecentNotebook>,
+) -> Result<(), String> {
+    let submenu = &state.submenu;
+    for item in submenu.items().map_err(|e| e.to_string())? {
+        submenu.remove(&item).map_err(|e| e.to_string())?;
+    }
+
+    let mut ids = Vec::with_capacity(items.len());
+    for (i, notebook) in items.iter().enumerate() {
+        let item = MenuItem::with_id(&app, format!("recent_{i}"), &notebook.name, true, None::<&str>)
+            .map_err(|e| e.to_string())?;
+        submenu.append(&item).map_err(|e| e.to_string())?;
+        ids.push(notebook.id.clone());
+    }
+    *state.ids.lock().unwrap() = ids;
+
+    if !items.is_empty() {
+        submenu
+            .append(&PredefinedMenuItem::separator(&app).map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+    }
+    let clear = MenuItem::with_id(&app, "recent_clear", "Clear Menu", true, None::<&str>)
+        .map_err(|e| e.to_string())?;
+    submenu.append(&clear).map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// The entity id targeted by the most recently popped-up sidebar context menu, so the
+/// fixed-id menu items it's built from (e.g. `ctx_rename`) can carry it onto the stable
+/// `menu://context-action` event once a choice is made.
+struct ContextMenuTarget(Mutex<Option<String>>);
+
+/// Payload for the stable `menu://context-action` event.
+#[derive(Clone, serde::Serialize)]
+struct ContextMenuActionEvent {
+    action: String,
+    entity_id: String,
+}
+
+/// Pops up a native right-click menu for a sidebar entry at the cursor. The menu items
+/// use fixed ids (frontend listeners need a stable event name to subscribe to); the
+/// target entity id is stashed in `ContextMenuTarget` and attached when `on_menu_event`
+/// emits `menu://context-action`.
+#[tauri::command]
+fn context_menu(
+    app: tauri::AppHandle,
+    state: tauri::State<ContextMenuTarget>,
+    window: tauri::Window,
+    entity_id: String,
+) -> tauri::Result<()> {
+    *state.0.lock().unwrap() = Some(entity_id);
+
+    let new_note = MenuItem::with_id(&app, "ctx_new_note", "New Note", true, None::<&str>)?;
+    let rename = MenuItem::with_id(&app, "ctx_rename", "Rename", true, None::<&str>)?;
+    let duplicate = MenuItem::with_id(&app, "ctx_duplicate", "Duplicate", true, None::<&str>)?;
+    let export = MenuItem::with_id(&app, "ctx_export", "Export Note...", true, None::<&str>)?;
+    let delete = MenuItem::with_id(&app, "ctx_delete", "Delete", true, None::<&str>)?;
+
+    let menu = Menu::with_items(
+        &app,
+        &[
+            &new_note,
+            &rename,
+            &duplicate,
+            &export,
+            &PredefinedMenuItem::separator(&app)?,
+            &delete,
+        ],
+    )?;
+
+    menu.popup(window)
+}
+
+/// Submenus that plugins/extensions can append `MenuItem`s to at runtime, plus the
+/// ids handed out so far. Keyed by submenu name (e.g. "File", "Tools").
+struct MenuExtensions {
+    submenus: HashMap<String, Submenu<tauri::Wry>>,
+    registered_ids: Mutex<Vec<String>>,
+}
+
+#[tauri::command]
+fn register_menu_item(
+    app: tauri::AppHandle,
+    state: tauri::State<MenuExtensions>,
+    parent: String,
+    id: String,
+    label: String,
+    accelerator: Option<String>,
+) -> Result<(), String> {
+    let submenu = state
+        .submenus
+        .get(&parent)
+        .ok_or_else(|| format!("unknown menu \"{parent}\""))?;
+    let item = MenuItem::with_id(&app, &id, &label, true, accelerator.as_deref())
+        .map_err(|e| e.to_string())?;
+    submenu.append(&item).map_err(|e| e.to_string())?;
+    state.registered_ids.lock().unwrap().push(id);
+    Ok(())
+}
+
+/// Handles for the mutually-exclusive layout/view `CheckMenuItem`s, kept in Tauri
+/// state so `set_menu_state` can update their checked state as the frontend changes mode.
+struct MenuCheckItems {
+    single_pane: CheckMenuItem<tauri::Wry>,
+    double_pane: CheckMenuItem<tauri::Wry>,
+    triple_pane: CheckMenuItem<tauri::Wry>,
+    editor_only: CheckMenuItem<tauri::Wry>,
+    preview_only: CheckMenuItem<tauri::Wry>,
+    split_view: CheckMenuItem<tauri::Wry>,
+}
+
+#[tauri::command]
+fn set_menu_state(
+    state: tauri::State<MenuCheckItems>,
+    layout: Option<String>,
+    view: Option<String>,
+) -> Result<(), String> {
+    if let Some(layout) = layout {
+        state
+            .single_pane
+            .set_checked(layout == "single")
+            .map_err(|e| e.to_string())?;
+        state
+            .double_pane
+            .set_checked(layout == "double")
+            .map_err(|e| e.to_string())?;
+        state
+            .triple_pane
+            .set_checked(layout == "triple")
+            .map_err(|e| e.to_string())?;
+    }
+    if let Some(view) = view {
+        state
+            .editor_only
+            .set_checked(view == "editor")
+            .map_err(|e| e.to_string())?;
+        state
+            .preview_only
+            .set_checked(view == "preview")
+            .map_err(|e| e.to_string())?;
+        state
+            .split_view
+            .set_checked(view == "split")
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Whether closing the main window should hide it to the tray instead of quitting.
+/// Mirrors a setting persisted in the `tauri_plugin_sql` store; the frontend reads the
+/// persisted value on init and calls `set_close_to_tray` to sync it into this state,
+/// and again whenever the user changes the preference.
+struct CloseToTraySetting(AtomicBool);
+
+#[tauri::command]
+fn set_close_to_tray(state: tauri::State<CloseToTraySetting>, enabled: bool) {
+    state.0.store(enabled, Ordering::Relaxed);
+}
+
 fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
@@ -13,10 +208,29 @@ fn main() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_sql::Builder::new().build())
         .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(CloseToTraySetting(AtomicBool::new(false)))
+        .manage(ContextMenuTarget(Mutex::new(None)))
+        .invoke_handler(tauri::generate_handler![
+            set_close_to_tray,
+            set_menu_state,
+            register_menu_item,
+            context_menu,
+            update_recent
+        ])
         .setup(|app| {
             // Create the menu
-            let menu = create_menu(app.handle())?;
+            let (menu, check_items, menu_extensions, recent_menu) = create_menu(app.handle())?;
             app.set_menu(menu)?;
+            app.manage(check_items);
+            app.manage(menu_extensions);
+            app.manage(recent_menu);
+
+            // Create the tray icon so notes can be captured without the main window focused
+            create_tray(app.handle())?;
+
+            // Keep running in the background like a menubar app once closed to tray
+            #[cfg(target_os = "macos")]
+            app.set_activation_policy(tauri::ActivationPolicy::Regular);
 
             #[cfg(debug_assertions)]
             {
@@ -25,6 +239,19 @@ fn main() {
             }
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                if window.label() == "main"
+                    && window
+                        .state::<CloseToTraySetting>()
+                        .0
+                        .load(Ordering::Relaxed)
+                {
+                    let _ = window.hide();
+                    api.prevent_close();
+                }
+            }
+        })
         .on_menu_event(|app, event| {
             let window = app.get_webview_window("main").unwrap();
             match event.id().as_ref() {
@@ -64,14 +291,108 @@ fn main() {
                 "split_view" => {
                     let _ = window.eval("window.__NOTCH__.setEditorViewMode('split')");
                 }
-                _ => {}
+                id if id.starts_with("ctx_") => {
+                    let action = id.trim_start_matches("ctx_").to_string();
+                    let entity_id = app
+                        .state::<ContextMenuTarget>()
+                        .0
+                        .lock()
+                        .unwrap()
+                        .clone()
+                        .unwrap_or_default();
+                    let _ = app.emit("menu://context-action", ContextMenuActionEvent { action, entity_id });
+                }
+                "recent_clear" => {
+                    let _ = app.emit("menu://clear-recent", ());
+                }
+                id if id.starts_with("recent_") => {
+                    if let Some(notebook_id) = id
+                        .trim_start_matches("recent_")
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| app.state::<RecentMenuState>().ids.lock().unwrap().get(index).cloned())
+                    {
+                        let _ = app.emit("menu://open-recent", OpenRecentEvent { id: notebook_id });
+                    }
+                }
+                id => {
+                    // Dynamically registered extension items (tracked in
+                    // `MenuExtensions.registered_ids`) notify the webview via a
+                    // namespaced event instead of hardcoding behavior here. Everything
+                    // else (e.g. the static Help items) is intentionally ignored.
+                    let is_registered_extension = app
+                        .state::<MenuExtensions>()
+                        .registered_ids
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .any(|registered_id| registered_id == id);
+                    if is_registered_extension {
+                        let _ = app.emit(&format!("menu://{id}"), ());
+                    }
+                }
             }
         })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
 
-fn create_menu(handle: &tauri::AppHandle) -> Result<Menu<tauri::Wry>, tauri::Error> {
+fn create_tray(handle: &tauri::AppHandle) -> tauri::Result<()> {
+    let new_note = MenuItem::with_id(handle, "tray_new_note", "New Note", true, None::<&str>)?;
+    let toggle = MenuItem::with_id(handle, "tray_toggle", "Show/Hide Notch", true, None::<&str>)?;
+    let quit = MenuItem::with_id(handle, "tray_quit", "Quit", true, None::<&str>)?;
+    let menu = Menu::with_items(handle, &[&new_note, &toggle, &quit])?;
+
+    TrayIconBuilder::new()
+        .icon(handle.default_window_icon().unwrap().clone())
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| {
+            let window = app.get_webview_window("main").unwrap();
+            match event.id().as_ref() {
+                "tray_new_note" => {
+                    let _ = window.eval("window.__NOTCH__.newNote()");
+                }
+                "tray_toggle" => toggle_main_window(&window),
+                "tray_quit" => app.exit(0),
+                _ => {}
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                if let Some(window) = tray.app_handle().get_webview_window("main") {
+                    toggle_main_window(&window);
+                }
+            }
+        })
+        .build(handle)?;
+
+    Ok(())
+}
+
+fn toggle_main_window(window: &tauri::WebviewWindow) {
+    if window.is_visible().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+/// The layout/view mode the app starts in, matching `window.__NOTCH__`'s own startup
+/// default. The View menu's `CheckMenuItem`s derive their initial checked state from
+/// these single constants instead of each duplicating the assumption independently.
+const DEFAULT_LAYOUT_MODE: &str = "double";
+const DEFAULT_VIEW_MODE: &str = "split";
+
+fn create_menu(
+    handle: &tauri::AppHandle,
+) -> Result<(Menu<tauri::Wry>, MenuCheckItems, MenuExtensions, RecentMenuState), tauri::Error> {
     // App menu (macOS)
     let app_menu = Submenu::with_items(
         handle,
@@ -90,6 +411,9 @@ fn create_menu(handle: &tauri::AppHandle) -> Result<Menu<tauri::Wry>, tauri::Err
         ],
     )?;
 
+    // "Open Recent" starts empty and is rebuilt at runtime via `update_recent`
+    let open_recent = Submenu::with_items(handle, "Open Recent", true, &[])?;
+
     // File menu
     let file_menu = Submenu::with_items(
         handle,
@@ -98,6 +422,7 @@ fn create_menu(handle: &tauri::AppHandle) -> Result<Menu<tauri::Wry>, tauri::Err
         &[
             &MenuItem::with_id(handle, "new_note", "New Note", true, Some("CmdOrCtrl+N"))?,
             &MenuItem::with_id(handle, "new_notebook", "New Notebook", true, Some("CmdOrCtrl+Shift+N"))?,
+            &open_recent,
             &PredefinedMenuItem::separator(handle)?,
             &MenuItem::with_id(handle, "import", "Import Quiver Library...", true, Some("CmdOrCtrl+Shift+I"))?,
             &MenuItem::with_id(handle, "export", "Export Note...", true, Some("CmdOrCtrl+Shift+E"))?,
@@ -123,7 +448,56 @@ fn create_menu(handle: &tauri::AppHandle) -> Result<Menu<tauri::Wry>, tauri::Err
         ],
     )?;
 
-    // View menu
+    // View menu — initial checkmarks mirror DEFAULT_LAYOUT_MODE/DEFAULT_VIEW_MODE
+    let single_pane = CheckMenuItem::with_id(
+        handle,
+        "single_pane",
+        "Single Pane",
+        true,
+        DEFAULT_LAYOUT_MODE == "single",
+        Some("CmdOrCtrl+1"),
+    )?;
+    let double_pane = CheckMenuItem::with_id(
+        handle,
+        "double_pane",
+        "Two Panes",
+        true,
+        DEFAULT_LAYOUT_MODE == "double",
+        Some("CmdOrCtrl+2"),
+    )?;
+    let triple_pane = CheckMenuItem::with_id(
+        handle,
+        "triple_pane",
+        "Three Panes",
+        true,
+        DEFAULT_LAYOUT_MODE == "triple",
+        Some("CmdOrCtrl+3"),
+    )?;
+    let editor_only = CheckMenuItem::with_id(
+        handle,
+        "editor_only",
+        "Editor Only",
+        true,
+        DEFAULT_VIEW_MODE == "editor",
+        Some("CmdOrCtrl+4"),
+    )?;
+    let preview_only = CheckMenuItem::with_id(
+        handle,
+        "preview_only",
+        "Preview Only",
+        true,
+        DEFAULT_VIEW_MODE == "preview",
+        Some("CmdOrCtrl+5"),
+    )?;
+    let split_view = CheckMenuItem::with_id(
+        handle,
+        "split_view",
+        "Side by Side",
+        true,
+        DEFAULT_VIEW_MODE == "split",
+        Some("CmdOrCtrl+6"),
+    )?;
+
     let view_menu = Submenu::with_items(
         handle,
         "View",
@@ -131,18 +505,21 @@ fn create_menu(handle: &tauri::AppHandle) -> Result<Menu<tauri::Wry>, tauri::Err
         &[
             &MenuItem::with_id(handle, "toggle_sidebar", "Toggle Sidebar", true, Some("CmdOrCtrl+0"))?,
             &PredefinedMenuItem::separator(handle)?,
-            &MenuItem::with_id(handle, "single_pane", "Single Pane", true, Some("CmdOrCtrl+1"))?,
-            &MenuItem::with_id(handle, "double_pane", "Two Panes", true, Some("CmdOrCtrl+2"))?,
-            &MenuItem::with_id(handle, "triple_pane", "Three Panes", true, Some("CmdOrCtrl+3"))?,
+            &single_pane,
+            &double_pane,
+            &triple_pane,
             &PredefinedMenuItem::separator(handle)?,
-            &MenuItem::with_id(handle, "editor_only", "Editor Only", true, Some("CmdOrCtrl+4"))?,
-            &MenuItem::with_id(handle, "preview_only", "Preview Only", true, Some("CmdOrCtrl+5"))?,
-            &MenuItem::with_id(handle, "split_view", "Side by Side", true, Some("CmdOrCtrl+6"))?,
+            &editor_only,
+            &preview_only,
+            &split_view,
             &PredefinedMenuItem::separator(handle)?,
             &PredefinedMenuItem::fullscreen(handle, None)?,
         ],
     )?;
 
+    // Tools menu — empty by default, populated at runtime via `register_menu_item`
+    let tools_menu = Submenu::with_items(handle, "Tools", true, &[])?;
+
     // Window menu
     let window_menu = Submenu::with_items(
         handle,
@@ -166,15 +543,40 @@ fn create_menu(handle: &tauri::AppHandle) -> Result<Menu<tauri::Wry>, tauri::Err
         ],
     )?;
 
-    Menu::with_items(
+    let menu = Menu::with_items(
         handle,
         &[
             &app_menu,
             &file_menu,
             &edit_menu,
             &view_menu,
+            &tools_menu,
             &window_menu,
             &help_menu,
         ],
-    )
+    )?;
+
+    let mut submenus = HashMap::new();
+    submenus.insert("File".to_string(), file_menu);
+    submenus.insert("Tools".to_string(), tools_menu);
+
+    Ok((
+        menu,
+        MenuCheckItems {
+            single_pane,
+            double_pane,
+            triple_pane,
+            editor_only,
+            preview_only,
+            split_view,
+        },
+        MenuExtensions {
+            submenus,
+            registered_ids: Mutex::new(Vec::new()),
+        },
+        RecentMenuState {
+            submenu: open_recent,
+            ids: Mutex::new(Vec::new()),
+        },
+    ))
 }